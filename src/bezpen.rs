@@ -1,6 +1,31 @@
 use crate::wonkiness::wonkiness;
-use kurbo::{BezPath, ParamCurveArclen};
+use clap::ValueEnum;
+use kurbo::{flatten, BezPath, CubicBez, Line, ParamCurveArclen, PathEl, Point, Shape};
 use skrifa::outline::OutlinePen;
+use write_fonts::tables::glyf::{MalformedPath, SimpleGlyph};
+
+/// Which winding rule to use when asking skia to simplify an outline.
+///
+/// TrueType and CFF outlines use the nonzero rule, so counters (the hole in
+/// an "o", say) only read as holes because they wind the opposite direction
+/// to their outer contour; even-odd ignores direction entirely.
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub(crate) enum FillRule {
+    #[default]
+    #[value(alias = "nonzero")]
+    NonZero,
+    #[value(alias = "evenodd")]
+    EvenOdd,
+}
+
+impl FillRule {
+    fn to_skia(self) -> skia_safe::PathFillType {
+        match self {
+            FillRule::NonZero => skia_safe::PathFillType::Winding,
+            FillRule::EvenOdd => skia_safe::PathFillType::EvenOdd,
+        }
+    }
+}
 
 #[derive(Debug, Default)]
 pub(crate) struct Paths {
@@ -30,8 +55,9 @@ impl OutlinePen for Paths {
     }
 }
 
-fn bezpath_to_skia_path(bez: &BezPath) -> skia_safe::Path {
+fn bezpath_to_skia_path(bez: &BezPath, fill_rule: FillRule) -> skia_safe::Path {
     let mut path = skia_safe::Path::new();
+    path.set_fill_type(fill_rule.to_skia());
     for el in bez.elements() {
         match el {
             kurbo::PathEl::MoveTo(p) => {
@@ -102,22 +128,193 @@ fn skia_path_to_bezpath(path: &skia_safe::Path) -> BezPath {
     }
     bez
 }
+/// Replace every cubic segment of `path` with a series of quadratics,
+/// since TrueType `SimpleGlyph`s can't represent cubics.
+fn cubics_to_quads(path: &BezPath, accuracy: f64) -> BezPath {
+    let mut out = BezPath::new();
+    let mut current = Point::ORIGIN;
+    for el in path.elements() {
+        match *el {
+            PathEl::MoveTo(p) => {
+                out.move_to(p);
+                current = p;
+            }
+            PathEl::LineTo(p) => {
+                out.line_to(p);
+                current = p;
+            }
+            PathEl::QuadTo(p1, p2) => {
+                out.quad_to(p1, p2);
+                current = p2;
+            }
+            PathEl::CurveTo(p1, p2, p3) => {
+                let cubic = CubicBez::new(current, p1, p2, p3);
+                for (_, _, quad) in cubic.to_quads(accuracy) {
+                    out.quad_to(quad.p1, quad.p2);
+                }
+                current = p3;
+            }
+            PathEl::ClosePath => out.close_path(),
+        }
+    }
+    out
+}
+
+/// Flatten a contour into a polyline, closing it if it ends with `ClosePath`.
+fn flatten_to_lines(path: &BezPath, tolerance: f64) -> Vec<Line> {
+    let mut lines = vec![];
+    let mut start = Point::ORIGIN;
+    let mut current = Point::ORIGIN;
+    flatten(path, tolerance, |el| match el {
+        PathEl::MoveTo(p) => {
+            start = p;
+            current = p;
+        }
+        PathEl::LineTo(p) => {
+            lines.push(Line::new(current, p));
+            current = p;
+        }
+        PathEl::ClosePath => {
+            if current != start {
+                lines.push(Line::new(current, start));
+            }
+            current = start;
+        }
+        _ => unreachable!("kurbo::flatten only emits MoveTo/LineTo/ClosePath"),
+    });
+    lines
+}
+
+/// Below this, a cross product is treated as zero (the two points it was
+/// computed from are considered collinear rather than strictly on one side).
+const COLLINEAR_EPSILON: f64 = 1e-6;
+
+/// Do two (non-adjacent) line segments cross at an interior point, or lie on
+/// top of one another for some nonzero stretch (e.g. duplicate contours, or a
+/// contour that doubles back on itself)?
+fn segments_cross(a: Line, b: Line) -> bool {
+    fn side(o: Point, p: Point, q: Point) -> f64 {
+        (p.x - o.x) * (q.y - o.y) - (p.y - o.y) * (q.x - o.x)
+    }
+    let d1 = side(b.p0, b.p1, a.p0);
+    let d2 = side(b.p0, b.p1, a.p1);
+    let d3 = side(a.p0, a.p1, b.p0);
+    let d4 = side(a.p0, a.p1, b.p1);
+    if (d1 > 0.0) != (d2 > 0.0) && (d3 > 0.0) != (d4 > 0.0) {
+        return true;
+    }
+    if d1.abs() < COLLINEAR_EPSILON
+        && d2.abs() < COLLINEAR_EPSILON
+        && d3.abs() < COLLINEAR_EPSILON
+        && d4.abs() < COLLINEAR_EPSILON
+    {
+        return collinear_segments_overlap(a, b);
+    }
+    false
+}
+
+/// Do two collinear segments overlap along their shared line, rather than
+/// merely touching at a single point?
+fn collinear_segments_overlap(a: Line, b: Line) -> bool {
+    let axis = a.p1 - a.p0;
+    let project = |p: Point| (p.x - a.p0.x) * axis.x + (p.y - a.p0.y) * axis.y;
+    let (a0, a1) = (project(a.p0), project(a.p1));
+    let (b0, b1) = (project(b.p0), project(b.p1));
+    let (a_min, a_max) = (a0.min(a1), a0.max(a1));
+    let (b_min, b_max) = (b0.min(b1), b0.max(b1));
+    a_min.max(b_min) < a_max.min(b_max)
+}
+
+fn bboxes_overlap(a: Line, b: Line) -> bool {
+    // Inclusive on every side, so horizontal and vertical segments (whose
+    // boxes have zero width or height) still count as overlapping.
+    let (ra, rb) = (a.bounding_box(), b.bounding_box());
+    ra.x0 <= rb.x1 && rb.x0 <= ra.x1 && ra.y0 <= rb.y1 && rb.y0 <= ra.y1
+}
+
+fn contour_self_intersects(lines: &[Line]) -> bool {
+    let n = lines.len();
+    for i in 0..n {
+        // Segments adjacent in the contour (including the wrap-around pair)
+        // share an endpoint, which isn't an overlap.
+        for j in (i + 1)..n {
+            if j == i + 1 || (i == 0 && j == n - 1) {
+                continue;
+            }
+            if bboxes_overlap(lines[i], lines[j]) && segments_cross(lines[i], lines[j]) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn contours_intersect(a: &[Line], b: &[Line]) -> bool {
+    a.iter()
+        .any(|&la| b.iter().any(|&lb| bboxes_overlap(la, lb) && segments_cross(la, lb)))
+}
+
 impl Paths {
-    pub fn wonkiness(&self) -> f32 {
-        let mut cleaned = vec![];
-        // Prep the path. First split into closed paths
+    /// The underlying decomposed outline.
+    pub(crate) fn path(&self) -> &BezPath {
+        &self.path
+    }
+
+    /// Split this path into its closed subpaths (contours), one per `moveto`.
+    fn contours(&self) -> Vec<BezPath> {
+        let mut contours = vec![];
         for el in self.path.elements() {
             if matches!(el, kurbo::PathEl::MoveTo(_)) {
-                cleaned.push(BezPath::new());
+                contours.push(BezPath::new());
             }
-            cleaned.last_mut().unwrap().push(*el);
+            contours.last_mut().unwrap().push(*el);
         }
-        cleaned.iter().map(wonkiness).sum::<f32>()
+        contours
+    }
+
+    pub fn wonkiness(&self) -> f32 {
+        self.contours().iter().map(wonkiness).sum::<f32>()
+    }
+
+    /// The locations and individual contributions of every inter-segment
+    /// junction with a nonzero contribution across all contours, for
+    /// rendering a visual diff report.
+    pub(crate) fn wonkiness_junctions(&self) -> Vec<(kurbo::Point, f32)> {
+        self.contours()
+            .iter()
+            .flat_map(|c| crate::wonkiness::wonkiness_detailed(c).1)
+            .collect()
     }
 
-    pub(crate) fn remove_overlaps(&self) -> Paths {
+    /// Does this path actually self-overlap, or cross between contours?
+    ///
+    /// Returns `false` fast when no contour self-intersects and no pair of
+    /// contours crosses one another, so callers can skip the (comparatively
+    /// expensive) overlap-removal/wonkiness comparison entirely.
+    pub(crate) fn overlaps(&self) -> bool {
+        let contours: Vec<Vec<Line>> = self
+            .contours()
+            .iter()
+            .map(|c| flatten_to_lines(c, 0.1))
+            .collect();
+
+        for (i, lines) in contours.iter().enumerate() {
+            if contour_self_intersects(lines) {
+                return true;
+            }
+            if contours[i + 1..]
+                .iter()
+                .any(|other| contours_intersect(lines, other))
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    pub(crate) fn remove_overlaps(&self, fill_rule: FillRule) -> Paths {
         // println!("Kurbo path: {:?}", self.path.to_svg());
-        let skia_path = bezpath_to_skia_path(&self.path);
+        let skia_path = bezpath_to_skia_path(&self.path, fill_rule);
         // println!("Path: {:?}", skia_path.to_svg());
         // println!(
         //     "Simplified: {:?}",
@@ -128,6 +325,13 @@ impl Paths {
             .unwrap_or(self.path.clone());
         Paths { path: simple }
     }
+
+    /// Convert this path into a TrueType `SimpleGlyph`, first flattening any
+    /// cubic segments (e.g. left over from overlap removal) into quadratics.
+    pub(crate) fn to_simple_glyph(&self) -> Result<SimpleGlyph, MalformedPath> {
+        let quads = cubics_to_quads(&self.path, 1.0);
+        SimpleGlyph::from_kurbo(&quads)
+    }
 }
 
 #[cfg(test)]
@@ -141,18 +345,65 @@ mod tests {
             path: BezPath::from_svg("M 100 100 L 100 200 L 120 200 L 120 100 L 100 100 Z M 75 150 L 75 175 L 150 175L 150 150 L 75 150 Z").unwrap(),
         };
         assert_relative_eq!(cross.wonkiness(), 0.0);
-        let removed = cross.remove_overlaps();
+        let removed = cross.remove_overlaps(FillRule::NonZero);
         println!("Removed: {:?}", removed.path.to_svg());
         assert_relative_eq!(removed.wonkiness(), 0.0);
     }
 
+    #[test]
+    fn test_cross_overlaps() {
+        // The two rectangles making up the cross genuinely cross each other.
+        let cross = Paths {
+            path: BezPath::from_svg("M 100 100 L 100 200 L 120 200 L 120 100 L 100 100 Z M 75 150 L 75 175 L 150 175L 150 150 L 75 150 Z").unwrap(),
+        };
+        assert!(cross.overlaps());
+    }
+
+    #[test]
+    fn test_disjoint_squares_do_not_overlap() {
+        // Two separate, non-touching squares don't overlap.
+        let squares = Paths {
+            path: BezPath::from_svg("M 0 0 L 0 10 L 10 10 L 10 0 Z M 100 100 L 100 110 L 110 110 L 110 100 Z")
+                .unwrap(),
+        };
+        assert!(!squares.overlaps());
+    }
+
+    #[test]
+    fn test_simple_square_does_not_self_overlap() {
+        let square = Paths {
+            path: BezPath::from_svg("M 0 0 L 0 10 L 10 10 L 10 0 Z").unwrap(),
+        };
+        assert!(!square.overlaps());
+    }
+
+    #[test]
+    fn test_duplicate_contour_overlaps() {
+        // Two exactly-coincident contours don't cross transversally anywhere,
+        // but they're still an overlap that should be flagged.
+        let duplicated = Paths {
+            path: BezPath::from_svg("M 0 0 L 0 10 L 10 10 L 10 0 Z M 0 0 L 0 10 L 10 10 L 10 0 Z")
+                .unwrap(),
+        };
+        assert!(duplicated.overlaps());
+    }
+
+    #[test]
+    fn test_figure_eight_self_overlaps() {
+        // A single contour that crosses itself.
+        let figure_eight = Paths {
+            path: BezPath::from_svg("M 0 0 L 10 10 L 10 0 L 0 10 Z").unwrap(),
+        };
+        assert!(figure_eight.overlaps());
+    }
+
     #[test]
     fn test_dagger_not_much_wonkier() {
         let dagger = Paths {
             path: BezPath::from_svg("M 100 100 L 100 200 L 120 200 L 120 50 L 100 100 Z M 75 150 L 75 175 L 150 175L 150 150 L 75 150 Z").unwrap()
         };
         let before = dagger.wonkiness();
-        let removed = dagger.remove_overlaps();
+        let removed = dagger.remove_overlaps(FillRule::NonZero);
         println!("Removed: {:?}", removed.path.to_svg());
         let after = removed.wonkiness();
         assert_relative_eq!(before, after, epsilon = 0.1);
@@ -167,7 +418,7 @@ mod tests {
             .unwrap(),
         };
         let before = upoint.wonkiness();
-        let removed = upoint.remove_overlaps();
+        let removed = upoint.remove_overlaps(FillRule::NonZero);
         println!("Removed: {:?}", removed.path.to_svg());
         let after = removed.wonkiness();
         let change = (after / before - 1.0) * 100.0;
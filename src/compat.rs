@@ -0,0 +1,139 @@
+use crate::{bezpen::Paths, gid_to_name};
+use kurbo::{BezPath, PathEl, Point};
+use read_fonts::TableProvider;
+use skrifa::{
+    instance::{LocationRef, Size},
+    outline::DrawSettings,
+    FontRef, GlyphId, MetadataProvider,
+};
+use std::collections::BTreeSet;
+
+/// The "shape" of a contour, ignoring coordinates: just the sequence of
+/// command kinds. Two outlines interpolate only if, contour for contour,
+/// their shapes match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ElKind {
+    Move,
+    Line,
+    Quad,
+    Cubic,
+    Close,
+}
+
+fn shape(path: &BezPath) -> Vec<Vec<ElKind>> {
+    let mut contours: Vec<Vec<ElKind>> = vec![];
+    for el in path.elements() {
+        match el {
+            PathEl::MoveTo(_) => contours.push(vec![ElKind::Move]),
+            PathEl::LineTo(_) => contours.last_mut().unwrap().push(ElKind::Line),
+            PathEl::QuadTo(..) => contours.last_mut().unwrap().push(ElKind::Quad),
+            PathEl::CurveTo(..) => contours.last_mut().unwrap().push(ElKind::Cubic),
+            PathEl::ClosePath => contours.last_mut().unwrap().push(ElKind::Close),
+        }
+    }
+    contours
+}
+
+/// The index of the first contour at which two shapes diverge, if any.
+fn first_divergent_contour(a: &[Vec<ElKind>], b: &[Vec<ElKind>]) -> Option<usize> {
+    a.iter().zip(b.iter()).position(|(ca, cb)| ca != cb).or_else(|| (a.len() != b.len()).then_some(a.len().min(b.len())))
+}
+
+/// All the explicit on/off-curve coordinates in `path`, in command order,
+/// translated so the path's own centroid sits at the origin. This cancels
+/// out any overall sidebearing/position shift between instances so that
+/// only *structural* drift between masters contributes to the score.
+fn normalized_points(path: &BezPath) -> Vec<Point> {
+    let mut points = vec![];
+    for el in path.elements() {
+        match el {
+            PathEl::MoveTo(p) | PathEl::LineTo(p) => points.push(*p),
+            PathEl::QuadTo(p1, p2) => points.extend([*p1, *p2]),
+            PathEl::CurveTo(p1, p2, p3) => points.extend([*p1, *p2, *p3]),
+            PathEl::ClosePath => {}
+        }
+    }
+    let n = (points.len().max(1)) as f64;
+    let centroid = points.iter().fold(Point::ORIGIN, |acc, p| acc + p.to_vec2()) / n;
+    points.into_iter().map(|p| p - centroid.to_vec2()).collect()
+}
+
+/// Sum of squared distances between corresponding points of two
+/// structurally-compatible outlines.
+fn drift_score(a: &BezPath, b: &BezPath) -> f64 {
+    normalized_points(a)
+        .iter()
+        .zip(normalized_points(b).iter())
+        .map(|(pa, pb)| (pa.x - pb.x).powi(2) + (pa.y - pb.y).powi(2))
+        .sum()
+}
+
+/// For each glyph, draw its outline at every named instance and check that
+/// they're interpolation-compatible: same number of contours, same point
+/// count and on/off-curve structure per contour, same contour ordering.
+/// Incompatible glyphs report the first instance and contour at which they
+/// diverge from the first named instance that draws; compatible ones report a
+/// structural "drift" score summarising how much the masters move relative
+/// to one another.
+pub(crate) fn check_interpolation_compatibility(
+    fontref: &FontRef,
+    glyphs_to_check: &BTreeSet<String>,
+) {
+    let instances = fontref.named_instances();
+    if instances.is_empty() {
+        println!("Font has no named instances; nothing to check for interpolation compatibility.");
+        return;
+    }
+
+    let outlines = fontref.outline_glyphs();
+    let glyph_count = fontref
+        .maxp()
+        .map(|maxp| maxp.num_glyphs())
+        .unwrap_or_default();
+
+    for gid in 0..glyph_count {
+        let glyph_id = GlyphId::new(gid);
+        let glyphname = gid_to_name(fontref, glyph_id);
+        if !glyphs_to_check.is_empty() && !glyphs_to_check.contains(&glyphname) {
+            continue;
+        }
+        let Some(glyph) = outlines.get(glyph_id) else {
+            continue;
+        };
+
+        let mut reference: Option<(BezPath, Vec<Vec<ElKind>>)> = None;
+        let mut drift = 0.0;
+        let mut compatible = true;
+        for (idx, instance) in instances.iter().enumerate() {
+            let owned_location = instance.location();
+            let location: LocationRef = (&owned_location).into();
+            let settings = DrawSettings::unhinted(Size::unscaled(), location);
+            let mut paths = Paths::default();
+            if glyph.draw(settings, &mut paths).is_err() {
+                continue;
+            }
+            let path = paths.path().clone();
+            let path_shape = shape(&path);
+
+            match &reference {
+                None => reference = Some((path, path_shape)),
+                Some((ref_path, ref_shape)) => {
+                    if &path_shape != ref_shape {
+                        let contour = first_divergent_contour(ref_shape, &path_shape).unwrap_or(0);
+                        println!(
+                            " Glyph {} is not interpolation-compatible: instance {} diverges at contour {}",
+                            glyphname, idx, contour
+                        );
+                        compatible = false;
+                        break;
+                    }
+                    drift += drift_score(ref_path, &path);
+                }
+            }
+        }
+
+        if compatible && drift > 0.0 {
+            println!(" Glyph {} drift across instances: {:.2}", glyphname, drift);
+        }
+    }
+}
@@ -1,7 +1,10 @@
 mod bezpen;
+mod compat;
+mod repair;
+mod report;
 mod wonkiness;
-use bezpen::Paths;
-use read_fonts::{tables::glyf::Glyph, TableProvider};
+use bezpen::{FillRule, Paths};
+use read_fonts::TableProvider;
 use std::{collections::BTreeSet, path::PathBuf};
 
 use clap::Parser;
@@ -23,11 +26,31 @@ struct Cli {
     #[clap(long = "glyphset")]
     glyphset: Option<String>,
 
+    /// Write a repaired font to this path, replacing each overlapping simple
+    /// glyph that stays within the wonkiness tolerance with its
+    /// overlap-removed outline
+    #[clap(long = "output")]
+    output: Option<PathBuf>,
+
+    /// The winding rule to use when simplifying outlines
+    #[clap(long = "fill-rule", value_enum, default_value = "non-zero")]
+    fill_rule: FillRule,
+
+    /// Instead of testing for overlap wonkiness, check that each glyph's
+    /// outline is interpolation-compatible across the font's named instances
+    #[clap(long = "check-interpolation")]
+    check_interpolation: bool,
+
+    /// For each flagged glyph, write an SVG to this directory showing the
+    /// original and overlap-removed outlines with markers at wonky junctions
+    #[clap(long = "report")]
+    report: Option<PathBuf>,
+
     /// The font file to compare
     font: PathBuf,
 }
 
-fn gid_to_name<'a>(font: &impl TableProvider<'a>, gid: GlyphId) -> String {
+pub(crate) fn gid_to_name<'a>(font: &impl TableProvider<'a>, gid: GlyphId) -> String {
     if let Ok(Some(name)) = font
         .post()
         .map(|post| post.glyph_name(gid).map(|x| x.to_string()))
@@ -53,12 +76,32 @@ fn main() {
     } else {
         BTreeSet::new()
     };
+
+    if cli.check_interpolation {
+        compat::check_interpolation_compatibility(&fontref, &glyphs_to_check);
+        std::process::exit(0);
+    }
+
+    if let Some(output) = cli.output {
+        repair::write_repaired_font(
+            &fontref,
+            &glyphs_to_check,
+            cli.tolerance,
+            cli.fill_rule,
+            &output,
+        )
+        .expect("Couldn't write repaired font");
+        std::process::exit(0);
+    }
+
     if instances.is_empty() {
         test_font(
             &fontref,
             LocationRef::default(),
             &glyphs_to_check,
             cli.tolerance,
+            cli.fill_rule,
+            cli.report.as_deref(),
         );
         std::process::exit(0);
     }
@@ -84,6 +127,8 @@ fn main() {
             (&location).into(),
             &glyphs_to_check,
             cli.tolerance,
+            cli.fill_rule,
+            cli.report.as_deref(),
         );
     }
 }
@@ -92,6 +137,8 @@ fn test_font(
     location: LocationRef,
     glyphs_to_check: &BTreeSet<String>,
     tolerance: f32,
+    fill_rule: FillRule,
+    report_dir: Option<&std::path::Path>,
 ) {
     let outlines = fontref.outline_glyphs();
     let glyphcount = fontref
@@ -104,16 +151,19 @@ fn test_font(
         if glyphs_to_check.len() > 0 && !glyphs_to_check.contains(&glyphname) {
             continue;
         }
-        let glyph = fontref
-            .loca(None)
-            .unwrap()
-            .get_glyf(glyphid, &fontref.glyf().unwrap())
-            .expect("Couldn't read a glyph");
-        if matches!(glyph, Some(Glyph::Composite(_))) {
-            continue;
-        }
+        // `OutlineGlyphCollection` flattens composites into a single `BezPath`
+        // when drawing, so composite glyphs (accented letters, etc.) are
+        // compared on their decomposed outline just like simple glyphs.
         let settings = DrawSettings::unhinted(Size::unscaled(), location);
-        let comparison = compare_glyph(&outlines, settings, glyphid, tolerance);
+        let comparison = compare_glyph(
+            &outlines,
+            settings,
+            glyphid,
+            tolerance,
+            fill_rule,
+            &glyphname,
+            report_dir,
+        );
         if comparison > 0.0 && comparison < 1000.0 {
             println!(
                 " Wonkiness increased by {:.2}% in glyph {}",
@@ -123,11 +173,15 @@ fn test_font(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn compare_glyph(
     outlines: &OutlineGlyphCollection,
     settings: DrawSettings,
     glyph_id: GlyphId,
     tolerance: f32,
+    fill_rule: FillRule,
+    glyphname: &str,
+    report_dir: Option<&std::path::Path>,
 ) -> f32 {
     let glyph = outlines.get(glyph_id).unwrap();
 
@@ -135,11 +189,20 @@ fn compare_glyph(
     glyph
         .draw(settings, &mut paths)
         .expect("Couldn't draw glyph");
+    if !paths.overlaps() {
+        return 0.0;
+    }
     let total_wonkiness_before = paths.wonkiness();
     // println!("Total wonkiness before: {}", total_wonkiness_before);
-    let total_wonkiness_after = paths.remove_overlaps().wonkiness();
+    let cleaned = paths.remove_overlaps(fill_rule);
+    let total_wonkiness_after = cleaned.wonkiness();
     // println!("Total wonkiness after: {}", total_wonkiness_after);
     if total_wonkiness_after > (total_wonkiness_before) * (1.0 + tolerance) {
+        if let Some(dir) = report_dir {
+            if let Err(e) = report::write_glyph_report(dir, glyphname, &paths, &cleaned) {
+                eprintln!("Couldn't write report for glyph {}: {}", glyphname, e);
+            }
+        }
         (total_wonkiness_after / total_wonkiness_before - 1.0) * 100.0
     } else {
         0.0
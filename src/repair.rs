@@ -0,0 +1,126 @@
+use crate::{
+    bezpen::{FillRule, Paths},
+    gid_to_name,
+};
+use read_fonts::{tables::glyf::Glyph as RawGlyph, TableProvider};
+use skrifa::{
+    instance::{LocationRef, Size},
+    outline::DrawSettings,
+    FontRef, GlyphId, MetadataProvider,
+};
+use std::{collections::BTreeSet, path::Path};
+use write_fonts::{
+    from_obj::ToOwnedTable,
+    tables::{
+        glyf::{Glyf, Loca, LocaFormat},
+        head::Head,
+    },
+    FontBuilder,
+};
+
+/// Write a repaired copy of `fontref` to `output`. Every simple glyph that
+/// self-overlaps, and whose overlap-removed outline does not exceed the
+/// wonkiness tolerance, is replaced by its `remove_overlaps()` result;
+/// everything else (composites, glyphs that don't overlap, glyphs that fail
+/// the tolerance check, glyphs outside `glyphs_to_check`) is carried over
+/// unchanged.
+///
+/// Refuses to run on a variable font: overlap removal changes a glyph's
+/// point count, which would desync the `gvar` deltas computed against the
+/// original outline, silently corrupting every non-default instance.
+pub(crate) fn write_repaired_font(
+    fontref: &FontRef,
+    glyphs_to_check: &BTreeSet<String>,
+    tolerance: f32,
+    fill_rule: FillRule,
+    output: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if fontref.fvar().is_ok() || fontref.gvar().is_ok() {
+        return Err("Refusing to repair a variable font (it has fvar/gvar tables): \
+             overlap removal changes glyph point counts, which would desync the \
+             existing gvar deltas. Instantiate a static font first."
+            .into());
+    }
+
+    let outlines = fontref.outline_glyphs();
+    let loca = fontref.loca(None)?;
+    let glyf_table = fontref.glyf()?;
+    let glyph_count = fontref.maxp()?.num_glyphs();
+    let location = LocationRef::default();
+
+    let mut glyphs = Vec::with_capacity(glyph_count as usize);
+    for gid in 0..glyph_count {
+        let glyph_id = GlyphId::new(gid);
+        let glyphname = gid_to_name(fontref, glyph_id);
+        let raw_glyph = loca.get_glyf(glyph_id, &glyf_table)?;
+        let is_composite = matches!(raw_glyph, Some(RawGlyph::Composite(_)));
+        if !is_composite
+            && (glyphs_to_check.is_empty() || glyphs_to_check.contains(&glyphname))
+        {
+            if let Some(repaired) = repair_glyph(&outlines, location, glyph_id, tolerance, fill_rule) {
+                glyphs.push(repaired);
+                continue;
+            }
+        }
+        glyphs.push(
+            raw_glyph
+                .map(|g| g.to_owned_table())
+                .unwrap_or_default(),
+        );
+    }
+
+    let glyf = Glyf::new(glyphs);
+    let loca = Loca::new(glyf.iter_offsets());
+
+    // `FontBuilder` won't reconcile `head.indexToLocFormat` with the loca we
+    // just rebuilt, so do it ourselves or risk a loca the rest of the font
+    // can't parse.
+    let mut head: Head = fontref.head()?.to_owned_table();
+    head.index_to_loc_format = match loca.format() {
+        LocaFormat::Short => 0,
+        LocaFormat::Long => 1,
+    };
+
+    let mut builder = FontBuilder::new();
+    builder.copy_missing_tables(fontref);
+    builder.add_table(&glyf)?;
+    builder.add_table(&loca)?;
+    builder.add_table(&head)?;
+    std::fs::write(output, builder.build())?;
+    Ok(())
+}
+
+/// Try to produce a cleaned-up, overlap-free glyph. Returns `None` if the
+/// glyph isn't worth (or capable of) replacing, leaving the caller to fall
+/// back to the original.
+fn repair_glyph(
+    outlines: &skrifa::OutlineGlyphCollection,
+    location: LocationRef,
+    glyph_id: GlyphId,
+    tolerance: f32,
+    fill_rule: FillRule,
+) -> Option<write_fonts::tables::glyf::Glyph> {
+    let glyph = outlines.get(glyph_id)?;
+    let settings = DrawSettings::unhinted(Size::unscaled(), location);
+    let mut paths = Paths::default();
+    glyph.draw(settings, &mut paths).ok()?;
+
+    if !paths.overlaps() {
+        return None;
+    }
+
+    let before = paths.wonkiness();
+    let cleaned = paths.remove_overlaps(fill_rule);
+    let after = cleaned.wonkiness();
+    if after > before * (1.0 + tolerance) {
+        return None;
+    }
+
+    match cleaned.to_simple_glyph() {
+        Ok(simple) => Some(simple.into()),
+        Err(e) => {
+            log::warn!("Couldn't rebuild glyph {:?} as TrueType outlines: {:?}", glyph_id, e);
+            None
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::bezpen::Paths;
+use kurbo::{ParamCurve, ParamCurveDeriv, PathSeg, Point, Shape, Vec2};
+use std::{fs, path::Path};
+
+/// Find the segments meeting at `point` and return their incoming and
+/// outgoing tangent vectors, so the report can draw little "in"/"out"
+/// arrows at a wonky junction.
+fn tangents_at(path: &kurbo::BezPath, point: Point, epsilon: f64) -> (Vec2, Vec2) {
+    let segs: Vec<PathSeg> = path.segments().collect();
+    let incoming = segs
+        .iter()
+        .find(|seg| seg.end().distance(point) < epsilon)
+        .map(|seg| tangent(seg, 1.0))
+        .unwrap_or_default();
+    let outgoing = segs
+        .iter()
+        .find(|seg| seg.start().distance(point) < epsilon)
+        .map(|seg| tangent(seg, 0.0))
+        .unwrap_or_default();
+    (incoming, outgoing)
+}
+
+fn tangent(seg: &PathSeg, t: f64) -> Vec2 {
+    let deriv = match seg {
+        PathSeg::Line(line) => line.deriv().eval(t),
+        PathSeg::Quad(quad) => quad.deriv().eval(t),
+        PathSeg::Cubic(cubic) => cubic.deriv().eval(t),
+    }
+    .to_vec2();
+    if deriv.hypot2() > 0.0 {
+        deriv.normalize()
+    } else {
+        Vec2::default()
+    }
+}
+
+/// Write an SVG showing `original` (in red) overlaid with its overlap-removed
+/// outline `cleaned` (in green), with markers at the junctions of `cleaned`
+/// whose wonkiness contribution is above the mean of its nonzero junction
+/// contributions, so a type
+/// designer can see *where* overlap removal made the outline worse rather
+/// than just by how much overall.
+pub(crate) fn write_glyph_report(
+    dir: &Path,
+    glyph_name: &str,
+    original: &Paths,
+    cleaned: &Paths,
+) -> std::io::Result<()> {
+    fs::create_dir_all(dir)?;
+
+    let bbox = original
+        .path()
+        .bounding_box()
+        .union(cleaned.path().bounding_box());
+    let all_junctions = cleaned.wonkiness_junctions();
+    let mean_contribution = if all_junctions.is_empty() {
+        0.0
+    } else {
+        all_junctions.iter().map(|(_, c)| *c).sum::<f32>() / all_junctions.len() as f32
+    };
+    let junctions: Vec<(Point, f32)> = all_junctions
+        .into_iter()
+        .filter(|(_, contribution)| *contribution > mean_contribution)
+        .collect();
+
+    let mut svg = String::new();
+    svg.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n",
+        bbox.min_x(),
+        -bbox.max_y(),
+        bbox.width(),
+        bbox.height(),
+    ));
+    // Font space is Y-up; SVG is Y-down.
+    svg.push_str("<g transform=\"scale(1,-1)\">\n");
+    svg.push_str(&format!(
+        "<path d=\"{}\" fill=\"none\" stroke=\"red\" stroke-width=\"2\"/>\n",
+        original.path().to_svg(),
+    ));
+    svg.push_str(&format!(
+        "<path d=\"{}\" fill=\"none\" stroke=\"green\" stroke-width=\"2\"/>\n",
+        cleaned.path().to_svg(),
+    ));
+
+    let marker_radius = (bbox.width().max(bbox.height()) * 0.01).max(1.0);
+    let tangent_length = marker_radius * 4.0;
+    for (point, contribution) in &junctions {
+        let (in_tangent, out_tangent) = tangents_at(cleaned.path(), *point, marker_radius * 2.0);
+        svg.push_str(&format!(
+            "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{:.2}\" fill=\"orange\" opacity=\"0.8\"><title>{:.2}</title></circle>\n",
+            point.x, point.y, marker_radius, contribution,
+        ));
+        for tangent in [-in_tangent, out_tangent] {
+            let end = *point + tangent * tangent_length;
+            svg.push_str(&format!(
+                "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"blue\" stroke-width=\"1\"/>\n",
+                point.x, point.y, end.x, end.y,
+            ));
+        }
+    }
+    svg.push_str("</g>\n</svg>\n");
+
+    fs::write(dir.join(format!("{glyph_name}.svg")), svg)
+}
@@ -1,7 +1,8 @@
 use std::f64::consts::PI;
 
 use kurbo::{
-    BezPath, ParamCurve, ParamCurveArclen, ParamCurveCurvature, ParamCurveDeriv, PathSeg, Vec2,
+    BezPath, ParamCurve, ParamCurveArclen, ParamCurveCurvature, ParamCurveDeriv, ParamCurveExtrema,
+    PathSeg, Vec2,
 };
 
 trait SegCurvature {
@@ -32,8 +33,59 @@ impl SegCurvature for PathSeg {
     }
 }
 
+/// Parameter values at which curvature is worth sampling within a segment:
+/// its axis-aligned extrema (where its tangent is horizontal or vertical)
+/// plus the two endpoints.
+fn extrema_params(seg: &PathSeg) -> Vec<f64> {
+    let mut ts = vec![0.0];
+    match seg {
+        PathSeg::Line(line) => ts.extend(line.extrema()),
+        PathSeg::Quad(quad) => ts.extend(quad.extrema()),
+        PathSeg::Cubic(cubic) => ts.extend(cubic.extrema()),
+    }
+    ts.push(1.0);
+    ts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    ts.dedup();
+    ts
+}
+
+/// Curvature anomalies *within* a single segment: a sign change in curvature
+/// (an inflection hidden in the middle of a curve) that the endpoint-only,
+/// inter-segment comparison in [`wonkiness_detailed`] can't see. Each jump
+/// in curvature across a sign change between neighbouring samples is
+/// summed and normalized by arclength; curvature that varies without
+/// changing sign, as along any smooth curve, adds nothing. Samples where
+/// curvature is undefined (zero-length derivative) are skipped.
+fn intra_segment_wonkiness(seg: &PathSeg) -> f64 {
+    let len = seg.arclen(0.1);
+    if len <= 0.0 {
+        return 0.0;
+    }
+    let curvatures: Vec<f64> = extrema_params(seg)
+        .iter()
+        .map(|&t| seg.curvature(t))
+        .filter(|k| k.is_finite())
+        .collect();
+    let variation: f64 = curvatures
+        .windows(2)
+        .filter(|pair| pair[0] * pair[1] < 0.0)
+        .map(|pair| (pair[1] - pair[0]).abs())
+        .sum();
+    variation / len
+}
+
 pub(crate) fn wonkiness(path: &BezPath) -> f32 {
+    wonkiness_detailed(path).0
+}
+
+/// As [`wonkiness`], but also returns the contribution of each inter-segment
+/// junction whose contribution is nonzero, located at the point where the
+/// two segments meet. This lets
+/// callers (e.g. a diff report) point at *where* a path got wonkier, not
+/// just by how much.
+pub(crate) fn wonkiness_detailed(path: &BezPath) -> (f32, Vec<(kurbo::Point, f32)>) {
     let mut path_wonk = 0.0;
+    let mut junctions = vec![];
     log::debug!("\nConsidering path {:?}", path);
     let is_closed = if let Some(last_el) = path.elements().last() {
         matches!(last_el, kurbo::PathEl::ClosePath)
@@ -78,12 +130,21 @@ pub(crate) fn wonkiness(path: &BezPath) -> f32 {
             log::debug!("Angle between: {}°", angle_between.to_degrees());
             log::info!("curvaturediff: {}, anglediff: {}", curvaturediff, anglediff);
             log::debug!("Contribution: {}", contribution);
+            junctions.push((next_seg.start(), contribution as f32));
         }
         path_wonk += contribution;
     }
+
+    for seg in &segs {
+        let intra = intra_segment_wonkiness(seg);
+        if intra != 0.0 {
+            log::info!("intra-segment wonkiness for {:?}: {}", seg, intra);
+        }
+        path_wonk += intra;
+    }
     log::debug!("Total wonkiness: {}\n\n", path_wonk);
 
-    path_wonk as f32
+    (path_wonk as f32, junctions)
 }
 
 #[cfg(test)]
@@ -186,4 +247,44 @@ mod tests {
         let wonk2 = wonkiness(&path2);
         assert!(wonk2 < 1.1 * wonk1);
     }
+
+    #[test]
+    fn test_smooth_cubic_intra_segment_near_zero() {
+        // A single smooth cubic, with no internal inflection or cusp.
+        let path = BezPath::from_svg("M 0 0 C 0 0.3 0.3 1 1 1").unwrap();
+        assert!(wonkiness(&path) < 0.05);
+    }
+
+    #[test]
+    fn test_cubic_with_inflection_scores_high() {
+        // A smooth curve vs. an S-shaped cubic with an inflection point
+        // hidden in the middle of the segment: the endpoint tangents and
+        // curvatures look similar, but the inside of the curve is wonky.
+        let smooth = BezPath::from_svg("M 0 0 C 0 0.3 0.3 1 1 1").unwrap();
+        let inflected = BezPath::from_svg("M 0 0 C 1 1 -1 1 0 2").unwrap();
+        assert!(wonkiness(&inflected) > 5.0 * wonkiness(&smooth).max(0.01));
+    }
+
+    #[test]
+    fn test_subdividing_smooth_curve_does_not_inflate_score() {
+        // Splitting a smooth curve into more (still smooth) segments
+        // shouldn't make it look wonkier.
+        use kurbo::CubicBez;
+
+        let cubic = CubicBez::new((0.0, 0.0), (0.0, 0.3), (0.3, 1.0), (1.0, 1.0));
+        let mut whole = BezPath::new();
+        whole.move_to(cubic.p0);
+        whole.curve_to(cubic.p1, cubic.p2, cubic.p3);
+
+        let first_half = cubic.subsegment(0.0..0.5);
+        let second_half = cubic.subsegment(0.5..1.0);
+        let mut subdivided = BezPath::new();
+        subdivided.move_to(first_half.p0);
+        subdivided.curve_to(first_half.p1, first_half.p2, first_half.p3);
+        subdivided.curve_to(second_half.p1, second_half.p2, second_half.p3);
+
+        let whole_wonk = wonkiness(&whole);
+        let subdivided_wonk = wonkiness(&subdivided);
+        assert!(subdivided_wonk <= whole_wonk + 0.05);
+    }
 }